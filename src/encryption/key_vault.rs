@@ -0,0 +1,98 @@
+//! The "key vault" collection that stores Data Encryption Keys (DEKs), each wrapped by a
+//! Customer Master Key from a [`KmsProvider`](super::KmsProvider).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    bson::{doc, Binary},
+    error::Result,
+    options::{IndexOptions, UniqueIndex},
+    Collection,
+};
+
+use super::{generate_wrapped_data_key, KmsProvider};
+
+/// A single Data Encryption Key document, as stored in the key vault collection.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataKey {
+    #[serde(rename = "_id")]
+    pub id: Binary,
+    pub key_material: Binary,
+    pub creation_date: DateTime<Utc>,
+    pub update_date: DateTime<Utc>,
+    pub status: i32,
+    pub master_key: crate::bson::Document,
+    pub key_alt_names: Option<Vec<String>>,
+}
+
+/// A thin wrapper over the key vault collection: DEK provisioning and lookup-by-UUID.
+#[derive(Clone)]
+pub struct KeyVaultClient {
+    collection: Collection<DataKey>,
+}
+
+impl KeyVaultClient {
+    pub fn new(collection: Collection<DataKey>) -> Self {
+        Self { collection }
+    }
+
+    /// Ensure the key vault collection has the unique, partial index on `keyAltNames` the spec
+    /// requires so alternate key names can't collide.
+    pub async fn create_key_vault_index(&self) -> Result<()> {
+        self.collection
+            .create_index(
+                UniqueIndex::keys(doc! { "keyAltNames": 1 }),
+                IndexOptions::builder()
+                    .partial_filter_expression(doc! { "keyAltNames": { "$exists": true } })
+                    .build(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Generate a new DEK, wrap it with `kms_provider`'s CMK, insert it into the key vault, and
+    /// return the binary `_id` (UUID, subtype 4) used to reference it from an
+    /// [`EncryptionSchema`](super::EncryptionSchema).
+    pub async fn create_data_key(
+        &self,
+        kms_provider: &KmsProvider,
+        key_alt_names: Vec<String>,
+    ) -> Result<Binary> {
+        let id = Binary {
+            subtype: crate::bson::spec::BinarySubtype::Uuid,
+            bytes: Uuid::new_v4().as_bytes().to_vec(),
+        };
+        let wrapped = generate_wrapped_data_key(kms_provider);
+        let now = Utc::now();
+
+        let data_key = DataKey {
+            id: id.clone(),
+            key_material: Binary {
+                subtype: crate::bson::spec::BinarySubtype::Generic,
+                bytes: wrapped,
+            },
+            creation_date: now,
+            update_date: now,
+            status: 0,
+            master_key: doc! { "provider": "local" },
+            key_alt_names: if key_alt_names.is_empty() {
+                None
+            } else {
+                Some(key_alt_names)
+            },
+        };
+
+        self.collection.insert_one(data_key, None).await?;
+        Ok(id)
+    }
+
+    /// Look up a DEK by its binary `_id`.
+    pub async fn get_key_by_id(&self, id: &Binary) -> Result<Option<DataKey>> {
+        self.collection
+            .find_one(doc! { "_id": id.clone() }, None)
+            .await
+    }
+}
@@ -0,0 +1,438 @@
+//! Client-side field level encryption (CSFLE).
+//!
+//! Fields marked in an [`EncryptionSchema`] are transparently encrypted before a command is sent
+//! and decrypted on the way back out. Each field is encrypted with its own Data Encryption Key
+//! (DEK); DEKs live as documents in a "key vault" collection (see [`key_vault`]) and are
+//! themselves wrapped by a Customer Master Key (CMK) held by a [`KmsProvider`]. Encrypted values
+//! are stored as BSON binary subtype 6.
+
+pub mod key_vault;
+
+use std::collections::HashMap;
+
+use aes::Aes256;
+use cbc::{Decryptor, Encryptor};
+use cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha512;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    bson::{spec::BinarySubtype, Binary, Bson},
+    error::{ErrorKind, Result},
+    Namespace,
+};
+
+pub use self::key_vault::{DataKey, KeyVaultClient};
+
+type Aes256CbcEnc = Encryptor<Aes256>;
+type Aes256CbcDec = Decryptor<Aes256>;
+type HmacSha512 = Hmac<Sha512>;
+
+/// The length in bytes of a local KMS master key and of a generated Data Encryption Key: 32 bytes
+/// of HMAC key, 32 bytes of AES-256 key, and 32 bytes reserved, mirroring the key material layout
+/// the `AEAD_AES_256_CBC_HMAC_SHA_512` construction uses for both CMK and DEK.
+pub const LOCAL_MASTER_KEY_LEN: usize = 96;
+const DATA_KEY_LEN: usize = LOCAL_MASTER_KEY_LEN;
+
+const MAC_KEY_LEN: usize = 32;
+const ENC_KEY_LEN: usize = 32;
+const IV_LEN: usize = 16;
+const MAC_TAG_LEN: usize = 32;
+
+/// Where Customer Master Keys used to wrap DEKs are held.
+///
+/// Only `Local` is implemented today; the variants are laid out so that an `Aws` provider backed
+/// by AWS KMS can be added without reshaping callers.
+#[derive(Clone)]
+pub enum KmsProvider {
+    /// A 96-byte master key supplied directly by the caller, used for local testing.
+    Local { master_key: [u8; LOCAL_MASTER_KEY_LEN] },
+}
+
+impl KmsProvider {
+    fn master_key(&self) -> &[u8] {
+        match self {
+            KmsProvider::Local { master_key } => master_key,
+        }
+    }
+}
+
+/// The algorithm a field is encrypted with.
+///
+/// Deterministic encryption always produces the same ciphertext for the same plaintext and DEK,
+/// so the field remains queryable by equality; randomized encryption does not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    Deterministic,
+    Randomized,
+}
+
+impl Algorithm {
+    #[allow(dead_code)]
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::Deterministic => "AEAD_AES_256_CBC_HMAC_SHA_512-Deterministic",
+            Algorithm::Randomized => "AEAD_AES_256_CBC_HMAC_SHA_512-Random",
+        }
+    }
+
+    /// A single-byte tag identifying this algorithm, folded into the MAC as associated data so a
+    /// ciphertext can't be replayed under a different algorithm than it was encrypted with.
+    fn tag(self) -> u8 {
+        match self {
+            Algorithm::Deterministic => 0,
+            Algorithm::Randomized => 1,
+        }
+    }
+}
+
+/// Per-field encryption configuration, keyed by top-level field name (e.g. `"ssn"`). A dotted
+/// name such as `"address.zip"` is matched as a literal top-level key; it is not resolved into
+/// nested documents.
+#[derive(Clone)]
+pub struct EncryptionSchema {
+    fields: HashMap<String, FieldEncryption>,
+}
+
+#[derive(Clone)]
+struct FieldEncryption {
+    key_id: Binary,
+    algorithm: Algorithm,
+}
+
+impl EncryptionSchema {
+    pub fn new() -> Self {
+        Self {
+            fields: HashMap::new(),
+        }
+    }
+
+    /// Mark `field` as encrypted with `key_id` using `algorithm`.
+    pub fn encrypt_field(mut self, field: impl Into<String>, key_id: Binary, algorithm: Algorithm) -> Self {
+        self.fields.insert(field.into(), FieldEncryption { key_id, algorithm });
+        self
+    }
+}
+
+impl Default for EncryptionSchema {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Options used to construct a client that transparently encrypts/decrypts marked fields.
+#[derive(Clone)]
+pub struct ClientEncryptionOptions {
+    pub key_vault_namespace: Namespace,
+    pub kms_provider: KmsProvider,
+    pub schema: EncryptionSchema,
+}
+
+/// Encrypts and decrypts document fields according to an [`EncryptionSchema`], resolving DEKs
+/// through a [`KeyVaultClient`].
+pub struct ClientEncryption {
+    key_vault: KeyVaultClient,
+    kms_provider: KmsProvider,
+    schema: EncryptionSchema,
+}
+
+impl ClientEncryption {
+    pub fn new(key_vault: KeyVaultClient, options: ClientEncryptionOptions) -> Self {
+        Self {
+            key_vault,
+            kms_provider: options.kms_provider,
+            schema: options.schema,
+        }
+    }
+
+    /// Encrypt every field in `document` named in the schema, in place.
+    pub async fn encrypt_document(&self, document: &mut crate::bson::Document) -> Result<()> {
+        let fields: Vec<String> = self.schema.fields.keys().cloned().collect();
+        for field in fields {
+            if let Some(value) = document.remove(&field) {
+                let encrypted = self.encrypt_value(&field, value).await?;
+                document.insert(field, encrypted);
+            }
+        }
+        Ok(())
+    }
+
+    /// Encrypt a single value for `field`, looking up its DEK and algorithm in the schema.
+    pub async fn encrypt_value(&self, field: &str, value: Bson) -> Result<Bson> {
+        let config = self
+            .schema
+            .fields
+            .get(field)
+            .ok_or_else(|| ErrorKind::InvalidArgument {
+                message: format!("no encryption configured for field \"{}\"", field),
+            })?;
+
+        let data_key = self
+            .key_vault
+            .get_key_by_id(&config.key_id)
+            .await?
+            .ok_or_else(|| ErrorKind::InvalidArgument {
+                message: "data key referenced by schema not found in key vault".to_string(),
+            })?;
+
+        let dek = unwrap_data_key(&self.kms_provider, &data_key.key_material.bytes)?;
+        // BSON only encodes a document at the top level, so wrap the value in a one-field document.
+        let plaintext = crate::bson::to_vec(&crate::bson::doc! { "v": value })?;
+        let ciphertext = encrypt_with_dek(&dek, config.algorithm, &plaintext, &config.key_id.bytes);
+
+        Ok(Bson::Binary(Binary {
+            subtype: BinarySubtype::Encrypted,
+            bytes: ciphertext,
+        }))
+    }
+
+    /// Decrypt every encrypted (subtype 6) field in `document` named in the schema, in place.
+    pub async fn decrypt_document(&self, document: &mut crate::bson::Document) -> Result<()> {
+        let fields: Vec<String> = self.schema.fields.keys().cloned().collect();
+        for field in fields {
+            if let Some(Bson::Binary(binary)) = document.get(&field) {
+                if binary.subtype == BinarySubtype::Encrypted {
+                    let decrypted = self.decrypt_value(&field, binary.clone()).await?;
+                    document.insert(field, decrypted);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn decrypt_value(&self, field: &str, binary: Binary) -> Result<Bson> {
+        let config = self
+            .schema
+            .fields
+            .get(field)
+            .ok_or_else(|| ErrorKind::InvalidArgument {
+                message: format!("no encryption configured for field \"{}\"", field),
+            })?;
+
+        let data_key = self
+            .key_vault
+            .get_key_by_id(&config.key_id)
+            .await?
+            .ok_or_else(|| ErrorKind::InvalidArgument {
+                message: "data key referenced by schema not found in key vault".to_string(),
+            })?;
+
+        let dek = unwrap_data_key(&self.kms_provider, &data_key.key_material.bytes)?;
+        let plaintext = decrypt_with_dek(&dek, &binary.bytes, &config.key_id.bytes, config.algorithm)?;
+        let mut wrapper: crate::bson::Document = crate::bson::from_slice(&plaintext)?;
+        Ok(wrapper.remove("v").ok_or_else(|| ErrorKind::InvalidArgument {
+            message: "decrypted value is missing its wrapped field".to_string(),
+        })?)
+    }
+}
+
+/// Generate a new 96-byte DEK and wrap it with `kms_provider`'s CMK.
+pub(crate) fn generate_wrapped_data_key(kms_provider: &KmsProvider) -> Vec<u8> {
+    let mut dek = [0u8; DATA_KEY_LEN];
+    rand::thread_rng().fill_bytes(&mut dek);
+    wrap_data_key(kms_provider, &dek)
+}
+
+/// Associated data bound into the CMK wrap of every DEK; just a fixed context label, since a DEK
+/// has no per-field identity of its own.
+const DATA_KEY_WRAP_AAD: &[u8] = b"mongodb-local-cmk-wrap";
+
+fn wrap_data_key(kms_provider: &KmsProvider, dek: &[u8]) -> Vec<u8> {
+    aead_encrypt(kms_provider.master_key(), dek, random_iv(), DATA_KEY_WRAP_AAD)
+}
+
+fn unwrap_data_key(kms_provider: &KmsProvider, wrapped: &[u8]) -> Result<Vec<u8>> {
+    aead_decrypt(kms_provider.master_key(), wrapped, DATA_KEY_WRAP_AAD)
+}
+
+/// Associated data binding a field's ciphertext to the DEK and algorithm it was encrypted under,
+/// so a ciphertext can't be decrypted under a different key id or algorithm. The field name is
+/// not bound: two fields sharing the same DEK and algorithm accept each other's ciphertexts.
+fn field_aad(key_id: &[u8], algorithm: Algorithm) -> Vec<u8> {
+    let mut aad = key_id.to_vec();
+    aad.push(algorithm.tag());
+    aad
+}
+
+fn encrypt_with_dek(dek: &[u8], algorithm: Algorithm, plaintext: &[u8], key_id: &[u8]) -> Vec<u8> {
+    let (mac_key, _) = split_key(dek);
+    let iv = match algorithm {
+        // Derived from the key id and plaintext so the same plaintext under the same DEK always
+        // produces the same IV (and thus ciphertext), keeping the field queryable by equality.
+        Algorithm::Deterministic => deterministic_iv(mac_key, key_id, plaintext),
+        Algorithm::Randomized => random_iv(),
+    };
+    aead_encrypt(dek, plaintext, iv, &field_aad(key_id, algorithm))
+}
+
+fn decrypt_with_dek(dek: &[u8], ciphertext: &[u8], key_id: &[u8], algorithm: Algorithm) -> Result<Vec<u8>> {
+    aead_decrypt(dek, ciphertext, &field_aad(key_id, algorithm))
+}
+
+fn random_iv() -> [u8; IV_LEN] {
+    let mut iv = [0u8; IV_LEN];
+    rand::thread_rng().fill_bytes(&mut iv);
+    iv
+}
+
+fn deterministic_iv(mac_key: &[u8], key_id: &[u8], plaintext: &[u8]) -> [u8; IV_LEN] {
+    let mut mac = HmacSha512::new_from_slice(mac_key).expect("HMAC accepts keys of any length");
+    mac.update(key_id);
+    mac.update(plaintext);
+    let digest = mac.finalize().into_bytes();
+    let mut iv = [0u8; IV_LEN];
+    iv.copy_from_slice(&digest[..IV_LEN]);
+    iv
+}
+
+/// Splits 96 bytes of key material into its HMAC key (first 32 bytes) and AES-256 key (next 32
+/// bytes); the last 32 bytes are reserved.
+fn split_key(key: &[u8]) -> (&[u8], &[u8]) {
+    (&key[..MAC_KEY_LEN], &key[MAC_KEY_LEN..MAC_KEY_LEN + ENC_KEY_LEN])
+}
+
+/// Encrypt-then-MAC: AES-256-CBC under the key's AES key, tagged with an HMAC-SHA-512 (truncated
+/// to 32 bytes) over `aad || iv || ciphertext` under the key's HMAC key. `aad` binds the
+/// ciphertext to its intended context (e.g. a field's DEK and algorithm) so it can't be replayed
+/// elsewhere. Output is `iv || ciphertext || tag`.
+fn aead_encrypt(key: &[u8], plaintext: &[u8], iv: [u8; IV_LEN], aad: &[u8]) -> Vec<u8> {
+    let (mac_key, enc_key) = split_key(key);
+    let ciphertext = Aes256CbcEnc::new_from_slices(enc_key, &iv)
+        .expect("enc key and iv are always the correct length")
+        .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+
+    let mut mac = HmacSha512::new_from_slice(mac_key).expect("HMAC accepts keys of any length");
+    mac.update(aad);
+    mac.update(&iv);
+    mac.update(&ciphertext);
+    let tag = mac.finalize().into_bytes();
+
+    let mut out = Vec::with_capacity(IV_LEN + ciphertext.len() + MAC_TAG_LEN);
+    out.extend_from_slice(&iv);
+    out.extend_from_slice(&ciphertext);
+    out.extend_from_slice(&tag[..MAC_TAG_LEN]);
+    out
+}
+
+fn aead_decrypt(key: &[u8], blob: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    if blob.len() < IV_LEN + MAC_TAG_LEN {
+        return Err(ErrorKind::InvalidArgument {
+            message: "encrypted value is too short to contain an IV and MAC tag".to_string(),
+        }
+        .into());
+    }
+
+    let (mac_key, enc_key) = split_key(key);
+    let iv = &blob[..IV_LEN];
+    let ciphertext = &blob[IV_LEN..blob.len() - MAC_TAG_LEN];
+    let tag = &blob[blob.len() - MAC_TAG_LEN..];
+
+    let mut mac = HmacSha512::new_from_slice(mac_key).expect("HMAC accepts keys of any length");
+    mac.update(aad);
+    mac.update(iv);
+    mac.update(ciphertext);
+    let expected_tag = mac.finalize().into_bytes();
+
+    if expected_tag[..MAC_TAG_LEN].ct_eq(tag).unwrap_u8() != 1 {
+        return Err(ErrorKind::InvalidArgument {
+            message: "encrypted value failed integrity check".to_string(),
+        }
+        .into());
+    }
+
+    Aes256CbcDec::new_from_slices(enc_key, iv)
+        .expect("enc key and iv are always the correct length")
+        .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+        .map_err(|_| {
+            ErrorKind::InvalidArgument {
+                message: "failed to decrypt value".to_string(),
+            }
+            .into()
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn provider() -> KmsProvider {
+        KmsProvider::Local {
+            master_key: [7u8; LOCAL_MASTER_KEY_LEN],
+        }
+    }
+
+    #[test]
+    fn wrap_unwrap_data_key_roundtrips() {
+        let provider = provider();
+        let dek = vec![42u8; DATA_KEY_LEN];
+
+        let wrapped = wrap_data_key(&provider, &dek);
+        assert_ne!(wrapped, dek);
+
+        let unwrapped = unwrap_data_key(&provider, &wrapped).unwrap();
+        assert_eq!(unwrapped, dek);
+    }
+
+    #[test]
+    fn deterministic_encryption_is_stable_for_same_plaintext() {
+        let dek = vec![1u8; DATA_KEY_LEN];
+        let key_id = b"key-id".to_vec();
+        let plaintext = b"hunter2".to_vec();
+
+        let first = encrypt_with_dek(&dek, Algorithm::Deterministic, &plaintext, &key_id);
+        let second = encrypt_with_dek(&dek, Algorithm::Deterministic, &plaintext, &key_id);
+        assert_eq!(first, second);
+
+        assert_eq!(
+            decrypt_with_dek(&dek, &first, &key_id, Algorithm::Deterministic).unwrap(),
+            plaintext
+        );
+    }
+
+    #[test]
+    fn randomized_encryption_varies_for_same_plaintext() {
+        let dek = vec![1u8; DATA_KEY_LEN];
+        let key_id = b"key-id".to_vec();
+        let plaintext = b"hunter2".to_vec();
+
+        let first = encrypt_with_dek(&dek, Algorithm::Randomized, &plaintext, &key_id);
+        let second = encrypt_with_dek(&dek, Algorithm::Randomized, &plaintext, &key_id);
+        assert_ne!(first, second);
+
+        assert_eq!(
+            decrypt_with_dek(&dek, &first, &key_id, Algorithm::Randomized).unwrap(),
+            plaintext
+        );
+        assert_eq!(
+            decrypt_with_dek(&dek, &second, &key_id, Algorithm::Randomized).unwrap(),
+            plaintext
+        );
+    }
+
+    #[test]
+    fn tampered_ciphertext_fails_the_integrity_check() {
+        let dek = vec![1u8; DATA_KEY_LEN];
+        let mut ciphertext = encrypt_with_dek(&dek, Algorithm::Randomized, b"hunter2", b"key-id");
+        *ciphertext.last_mut().unwrap() ^= 0xff;
+
+        assert!(decrypt_with_dek(&dek, &ciphertext, b"key-id", Algorithm::Randomized).is_err());
+    }
+
+    #[test]
+    fn ciphertext_cannot_be_replayed_under_a_different_key_id() {
+        let dek = vec![1u8; DATA_KEY_LEN];
+        let ciphertext = encrypt_with_dek(&dek, Algorithm::Deterministic, b"hunter2", b"key-id-a");
+
+        assert!(decrypt_with_dek(&dek, &ciphertext, b"key-id-b", Algorithm::Deterministic).is_err());
+    }
+
+    #[test]
+    fn ciphertext_cannot_be_replayed_under_a_different_algorithm() {
+        let dek = vec![1u8; DATA_KEY_LEN];
+        let ciphertext = encrypt_with_dek(&dek, Algorithm::Deterministic, b"hunter2", b"key-id");
+
+        assert!(decrypt_with_dek(&dek, &ciphertext, b"key-id", Algorithm::Randomized).is_err());
+    }
+}
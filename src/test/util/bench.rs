@@ -0,0 +1,191 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use hdrhistogram::Histogram;
+use indicatif::{ProgressBar, ProgressStyle};
+use rand::Rng;
+use tokio::sync::mpsc;
+
+use crate::bson::doc;
+
+use super::TestClient;
+
+/// How long a single client in the benchmark harness keeps issuing operations.
+#[derive(Clone, Copy, Debug)]
+pub enum Workload {
+    /// Stop after performing exactly this many operations.
+    FixedCount(usize),
+    /// Keep performing operations until this much time has elapsed.
+    FixedDuration(Duration),
+}
+
+/// Configuration for a single run of the benchmark harness: `clients` concurrent workers issuing
+/// inserts/finds/updates/aggregations against a freshly initialized collection per `workload`.
+#[derive(Clone, Debug)]
+pub struct BenchmarkOptions {
+    /// Number of concurrent clients to spawn.
+    pub clients: usize,
+
+    /// Whether each client runs for a fixed number of operations or a fixed duration.
+    pub workload: Workload,
+
+    /// Number of distinct keys operations are drawn from; a smaller key space increases
+    /// contention on updates.
+    pub key_space_size: usize,
+
+    /// Size in bytes of the filler payload stored in each document.
+    pub value_size: usize,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        Self {
+            clients: 8,
+            workload: Workload::FixedCount(1_000),
+            key_space_size: 10_000,
+            value_size: 256,
+        }
+    }
+}
+
+/// Aggregate throughput and latency numbers produced by [`run_benchmark`].
+#[derive(Clone, Debug)]
+pub struct BenchmarkReport {
+    pub total_ops: u64,
+    pub failed_ops: u64,
+    pub throughput_ops_per_sec: f64,
+    pub p50_micros: u64,
+    pub p95_micros: u64,
+    pub p99_micros: u64,
+    pub max_micros: u64,
+}
+
+/// Drive a mixed insert/find/update/aggregate workload against a fresh collection and report
+/// throughput and latency percentiles. Reuses [`TestClient`] so the benchmark exercises the same
+/// pooling and dispatch path the correctness tests run against; a failed op is counted rather
+/// than aborting the run.
+pub async fn run_benchmark(options: BenchmarkOptions) -> BenchmarkReport {
+    let client = TestClient::new().await;
+    let coll = client
+        .init_db_and_coll("benchmark", "bench_harness")
+        .await;
+
+    let progress = match options.workload {
+        Workload::FixedCount(count) => {
+            let progress = ProgressBar::new((options.clients * count) as u64);
+            progress.set_style(
+                ProgressStyle::default_bar()
+                    .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} ({eta})"),
+            );
+            progress
+        }
+        Workload::FixedDuration(_) => {
+            let progress = ProgressBar::new_spinner();
+            progress.set_style(ProgressStyle::default_spinner().template("[{elapsed_precise}] {pos} ops {spinner}"));
+            progress
+        }
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<u64>();
+    let errors = Arc::new(AtomicU64::new(0));
+
+    let start = Instant::now();
+    let mut workers = Vec::with_capacity(options.clients);
+    for _ in 0..options.clients {
+        let coll = coll.clone();
+        let tx = tx.clone();
+        let opts = options.clone();
+        let errors = errors.clone();
+        workers.push(tokio::spawn(async move {
+            run_client(coll, opts, tx, errors).await;
+        }));
+    }
+    // Drop our own sender so the collector's recv loop ends once every worker finishes.
+    drop(tx);
+
+    let mut histogram = Histogram::<u64>::new(3).unwrap();
+    while let Some(micros) = rx.recv().await {
+        histogram.record(micros).unwrap();
+        progress.inc(1);
+    }
+
+    for worker in workers {
+        worker.await.unwrap();
+    }
+    progress.finish_and_clear();
+
+    let elapsed = start.elapsed();
+    let total_ops = histogram.len();
+
+    BenchmarkReport {
+        total_ops,
+        failed_ops: errors.load(Ordering::Relaxed),
+        throughput_ops_per_sec: total_ops as f64 / elapsed.as_secs_f64(),
+        p50_micros: histogram.value_at_quantile(0.50),
+        p95_micros: histogram.value_at_quantile(0.95),
+        p99_micros: histogram.value_at_quantile(0.99),
+        max_micros: histogram.max(),
+    }
+}
+
+async fn run_client(
+    coll: crate::Collection,
+    options: BenchmarkOptions,
+    tx: mpsc::UnboundedSender<u64>,
+    errors: Arc<AtomicU64>,
+) {
+    let filler: String = std::iter::repeat('x').take(options.value_size).collect();
+    let deadline = match options.workload {
+        Workload::FixedDuration(duration) => Some(Instant::now() + duration),
+        Workload::FixedCount(_) => None,
+    };
+
+    let mut i = 0usize;
+    loop {
+        match options.workload {
+            Workload::FixedCount(count) if i >= count => break,
+            Workload::FixedDuration(_) if Instant::now() >= deadline.unwrap() => break,
+            _ => {}
+        }
+
+        // `ThreadRng` isn't `Send`, so don't hold one across the awaits below.
+        let key = rand::thread_rng().gen_range(0..options.key_space_size as i64);
+        let started = Instant::now();
+
+        let result = match i % 4 {
+            0 => coll
+                .insert_one(doc! { "key": key, "value": &filler }, None)
+                .await
+                .map(|_| ()),
+            1 => coll.find_one(doc! { "key": key }, None).await.map(|_| ()),
+            2 => coll
+                .update_one(doc! { "key": key }, doc! { "$set": { "value": &filler } }, None)
+                .await
+                .map(|_| ()),
+            _ => coll
+                .aggregate(
+                    vec![doc! { "$match": { "key": key } }, doc! { "$limit": 1 }],
+                    None,
+                )
+                .await
+                .map(|_| ()),
+        };
+
+        match result {
+            Ok(()) => {
+                let elapsed = started.elapsed().as_micros() as u64;
+                let _ = tx.send(elapsed);
+            }
+            Err(_) => {
+                errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        i += 1;
+    }
+}
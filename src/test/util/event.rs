@@ -0,0 +1,193 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    event::cmap::CmapEventHandler,
+    event::command::{
+        CommandEventHandler,
+        CommandFailedEvent,
+        CommandStartedEvent,
+        CommandSucceededEvent,
+    },
+    options::ClientOptions,
+    Client,
+};
+
+/// A single command monitoring event, recorded in the order it was observed.
+#[derive(Clone, Debug)]
+pub enum CommandEvent {
+    Started(CommandStartedEvent),
+    Succeeded(CommandSucceededEvent),
+    Failed(CommandFailedEvent),
+}
+
+impl CommandEvent {
+    pub fn command_name(&self) -> &str {
+        match self {
+            CommandEvent::Started(event) => event.command_name.as_str(),
+            CommandEvent::Succeeded(event) => event.command_name.as_str(),
+            CommandEvent::Failed(event) => event.command_name.as_str(),
+        }
+    }
+}
+
+/// Consumes aggregated command metrics rather than raw events; can be backed by OpenTelemetry, a
+/// Prometheus registry, or anything else that fits this shape.
+pub trait MetricsRecorder: Send + Sync {
+    /// Record the duration of a completed command, keyed by command name.
+    fn record_duration(&self, command_name: &str, duration: Duration);
+
+    /// Record whether a completed command succeeded or failed.
+    fn record_result(&self, command_name: &str, succeeded: bool);
+
+    /// Update the number of commands currently in flight.
+    fn set_in_flight(&self, in_flight: i64);
+}
+
+/// The `command_event_handler` `EventClient` installs on its inner `Client`. Holds every event
+/// seen and optionally forwards aggregated metrics to a [`MetricsRecorder`].
+pub struct EventHandler {
+    events: Mutex<Vec<CommandEvent>>,
+    in_flight: Mutex<HashMap<i32, (String, Instant)>>,
+    recorder: Option<Arc<dyn MetricsRecorder>>,
+}
+
+impl Debug for EventHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventHandler").finish()
+    }
+}
+
+impl EventHandler {
+    pub fn new() -> Self {
+        Self {
+            events: Mutex::new(Vec::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            recorder: None,
+        }
+    }
+
+    /// Install a `MetricsRecorder` to receive aggregated command metrics as events arrive.
+    pub fn with_recorder(recorder: Arc<dyn MetricsRecorder>) -> Self {
+        Self {
+            events: Mutex::new(Vec::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            recorder: Some(recorder),
+        }
+    }
+
+    pub fn events(&self) -> Vec<CommandEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    fn push(&self, event: CommandEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn complete(&self, request_id: i32, command_name: &str, succeeded: bool) {
+        let started_at = self.in_flight.lock().unwrap().remove(&request_id);
+
+        if let Some(recorder) = &self.recorder {
+            if let Some((_, started_at)) = started_at {
+                recorder.record_duration(command_name, started_at.elapsed());
+            }
+            recorder.record_result(command_name, succeeded);
+            recorder.set_in_flight(self.in_flight.lock().unwrap().len() as i64);
+        }
+    }
+}
+
+impl Default for EventHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandEventHandler for EventHandler {
+    fn handle_command_started_event(&self, event: CommandStartedEvent) {
+        self.in_flight
+            .lock()
+            .unwrap()
+            .insert(event.request_id, (event.command_name.clone(), Instant::now()));
+        if let Some(recorder) = &self.recorder {
+            recorder.set_in_flight(self.in_flight.lock().unwrap().len() as i64);
+        }
+        self.push(CommandEvent::Started(event));
+    }
+
+    fn handle_command_succeeded_event(&self, event: CommandSucceededEvent) {
+        self.complete(event.request_id, &event.command_name, true);
+        self.push(CommandEvent::Succeeded(event));
+    }
+
+    fn handle_command_failed_event(&self, event: CommandFailedEvent) {
+        self.complete(event.request_id, &event.command_name, false);
+        self.push(CommandEvent::Failed(event));
+    }
+}
+
+// `TestClient` also installs this handler as its `cmap_event_handler`; CMAP events are ignored.
+impl CmapEventHandler for EventHandler {}
+
+/// A `Client` wrapper that records every command monitoring event it observes.
+#[derive(Clone)]
+pub struct EventClient {
+    client: Client,
+    handler: Arc<EventHandler>,
+}
+
+impl std::ops::Deref for EventClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
+
+impl EventClient {
+    pub async fn new() -> Self {
+        Self::with_options(None).await
+    }
+
+    pub async fn with_options(options: impl Into<Option<ClientOptions>>) -> Self {
+        Self::with_handler(Arc::new(EventHandler::new()), options).await
+    }
+
+    /// Construct an `EventClient` whose events are also forwarded to `recorder` for aggregated
+    /// metrics, in addition to being buffered for assertions.
+    pub async fn with_recorder(
+        recorder: Arc<dyn MetricsRecorder>,
+        options: impl Into<Option<ClientOptions>>,
+    ) -> Self {
+        Self::with_handler(Arc::new(EventHandler::with_recorder(recorder)), options).await
+    }
+
+    async fn with_handler(
+        handler: Arc<EventHandler>,
+        options: impl Into<Option<ClientOptions>>,
+    ) -> Self {
+        let mut options = options.into().unwrap_or_else(|| super::CLIENT_OPTIONS.clone());
+        options.command_event_handler = Some(handler.clone());
+
+        let client = Client::with_options(options).unwrap();
+        Self { client, handler }
+    }
+
+    pub fn events(&self) -> Vec<CommandEvent> {
+        self.handler.events()
+    }
+
+    pub fn command_started_events(&self, command_name: &str) -> Vec<CommandStartedEvent> {
+        self.events()
+            .into_iter()
+            .filter_map(|event| match event {
+                CommandEvent::Started(event) if event.command_name == command_name => Some(event),
+                _ => None,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,165 @@
+use serde::Serialize;
+
+use crate::{bson::Document, error::Result};
+
+use super::TestClient;
+
+/// A `configureFailPoint` command, used by tests to make the server misbehave in controlled
+/// ways: returning errors, delaying responses, or closing connections.
+#[derive(Clone, Debug, Serialize)]
+pub struct FailPoint {
+    #[serde(rename = "configureFailPoint")]
+    fail_point: String,
+    mode: FailPointMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<FailCommandOptions>,
+}
+
+impl FailPoint {
+    /// Build a `failCommand` failpoint that targets `fail_commands`.
+    pub fn fail_command(
+        fail_commands: &[&str],
+        mode: FailPointMode,
+        options: impl Into<Option<FailCommandOptions>>,
+    ) -> Self {
+        let mut options = options.into().unwrap_or_default();
+        options.fail_commands = fail_commands.iter().map(|s| s.to_string()).collect();
+
+        Self {
+            fail_point: "failCommand".to_string(),
+            mode,
+            data: Some(options),
+        }
+    }
+
+    pub(crate) async fn enable(self, client: &TestClient) -> Result<FailPointGuard> {
+        client
+            .database("admin")
+            .run_command(crate::bson::to_document(&self)?, None)
+            .await?;
+
+        Ok(FailPointGuard {
+            client: client.clone(),
+            fail_point_name: self.fail_point,
+        })
+    }
+}
+
+/// How many times (if at all) a failpoint should trip before disabling itself.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FailPointMode {
+    AlwaysOn,
+    Times(u32),
+    Off,
+}
+
+/// The `data` document of a `failCommand` failpoint. `block_connection`/`block_time_ms` delay the
+/// matched command before responding; `app_name` scopes the failpoint to a single client;
+/// `error_labels` are injected onto the synthesized error.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailCommandOptions {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fail_commands: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close_connection: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub write_concern_error: Option<Document>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_connection: Option<bool>,
+
+    #[serde(rename = "blockTimeMS", skip_serializing_if = "Option::is_none")]
+    pub block_time_ms: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_labels: Option<Vec<String>>,
+}
+
+impl FailCommandOptions {
+    pub fn builder() -> FailCommandOptionsBuilder {
+        FailCommandOptionsBuilder::default()
+    }
+}
+
+/// Builder for [`FailCommandOptions`]; mirrors the options struct field-for-field.
+#[derive(Default)]
+pub struct FailCommandOptionsBuilder {
+    options: FailCommandOptions,
+}
+
+impl FailCommandOptionsBuilder {
+    pub fn close_connection(mut self, close_connection: bool) -> Self {
+        self.options.close_connection = Some(close_connection);
+        self
+    }
+
+    pub fn error_code(mut self, error_code: i32) -> Self {
+        self.options.error_code = Some(error_code);
+        self
+    }
+
+    pub fn write_concern_error(mut self, write_concern_error: Document) -> Self {
+        self.options.write_concern_error = Some(write_concern_error);
+        self
+    }
+
+    /// Delay the matched command by `block_time_ms` before the server responds.
+    pub fn block_connection(mut self, block_time_ms: i64) -> Self {
+        self.options.block_connection = Some(true);
+        self.options.block_time_ms = Some(block_time_ms);
+        self
+    }
+
+    /// Only trip the failpoint for a client whose `appName` equals `app_name`.
+    pub fn app_name(mut self, app_name: impl Into<String>) -> Self {
+        self.options.app_name = Some(app_name.into());
+        self
+    }
+
+    /// Inject `labels` onto the error the matched command fails with.
+    pub fn error_labels(mut self, labels: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.options.error_labels = Some(labels.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn build(self) -> FailCommandOptions {
+        self.options
+    }
+}
+
+/// Disables the failpoint it was created from when dropped, so a test can't leak a misbehaving
+/// server configuration into the next one.
+pub struct FailPointGuard {
+    client: TestClient,
+    fail_point_name: String,
+}
+
+impl Drop for FailPointGuard {
+    fn drop(&mut self) {
+        let client = self.client.clone();
+        let fail_point_name = self.fail_point_name.clone();
+
+        // `run_command` drives the driver's Tokio-based networking, which needs a reactor;
+        // `futures::executor::block_on` doesn't provide one. Bridge through the crate's runtime
+        // handle, the same way connection pool cleanup does from its own `Drop` impl.
+        crate::runtime::block_on(async move {
+            let _ = client
+                .database("admin")
+                .run_command(
+                    crate::bson::doc! { "configureFailPoint": fail_point_name, "mode": "off" },
+                    None,
+                )
+                .await;
+        });
+    }
+}
@@ -0,0 +1,216 @@
+use crate::bson::{Bson, Document};
+
+/// Compares a value against an "expected" shape rather than for strict equality.
+pub trait Matchable {
+    fn matches(&self, expected: &Bson) -> bool;
+}
+
+impl Matchable for Document {
+    fn matches(&self, expected: &Bson) -> bool {
+        Bson::Document(self.clone()).matches(expected)
+    }
+}
+
+impl Matchable for Bson {
+    fn matches(&self, expected: &Bson) -> bool {
+        match_bson(self, expected)
+    }
+}
+
+/// Panics with a readable diff if `actual` does not match `expected`.
+pub fn assert_matches(actual: &Bson, expected: &Bson, description: impl Into<Option<&'static str>>) {
+    if !actual.matches(expected) {
+        panic!(
+            "{}expected (with placeholders) =\n{:#?}\n\nactual =\n{:#?}",
+            description
+                .into()
+                .map(|d| format!("{}\n\n", d))
+                .unwrap_or_default(),
+            expected,
+            actual,
+        );
+    }
+}
+
+fn match_bson(actual: &Bson, expected: &Bson) -> bool {
+    if let Bson::Document(expected_doc) = expected {
+        if let Some(operator) = Operator::parse(expected_doc) {
+            return operator.matches(Some(actual));
+        }
+    }
+
+    match (actual, expected) {
+        (Bson::Document(actual_doc), Bson::Document(expected_doc)) => {
+            match_document(actual_doc, expected_doc)
+        }
+        (Bson::Array(actual_arr), Bson::Array(expected_arr)) => {
+            actual_arr.len() == expected_arr.len()
+                && actual_arr
+                    .iter()
+                    .zip(expected_arr.iter())
+                    .all(|(a, e)| match_bson(a, e))
+        }
+        _ => actual == expected,
+    }
+}
+
+fn match_document(actual: &Document, expected: &Document) -> bool {
+    expected
+        .iter()
+        .all(|(key, expected_value)| field_matches(actual.get(key), expected_value))
+}
+
+/// Matches a single field given the actual value, if the field was present at all. Operator
+/// sentinels need to see absence, so this runs before falling back to [`match_bson`].
+fn field_matches(actual: Option<&Bson>, expected: &Bson) -> bool {
+    if let Bson::Document(expected_doc) = expected {
+        if let Some(operator) = Operator::parse(expected_doc) {
+            return operator.matches(actual);
+        }
+    }
+
+    match actual {
+        Some(actual) => match_bson(actual, expected),
+        None => false,
+    }
+}
+
+/// A unified-test-format style operator placeholder: a sub-document whose single key is a
+/// recognized `$$` sentinel, used in place of a concrete expected value.
+enum Operator {
+    /// `{ "$$exists": <bool> }` — the field must (or must not) be present.
+    Exists(bool),
+    /// `{ "$$type": "<type>" | ["<type>", ...] }` — the field must be present and of one of the
+    /// named BSON types.
+    Type(Vec<String>),
+    /// `{ "$$matchesAny": [<expected>, ...] }` — the field must be present and match at least one
+    /// of the alternatives.
+    MatchesAny(Vec<Bson>),
+    /// `{ "$$unsetOrMatches": <expected> }` — the field may be absent, or present and matching.
+    UnsetOrMatches(Bson),
+}
+
+impl Operator {
+    fn parse(doc: &Document) -> Option<Self> {
+        if doc.len() != 1 {
+            return None;
+        }
+        let (key, value) = doc.iter().next().unwrap();
+
+        match key.as_str() {
+            "$$exists" => value.as_bool().map(Operator::Exists),
+            "$$type" => Some(Operator::Type(match value {
+                Bson::Array(types) => types
+                    .iter()
+                    .filter_map(|t| t.as_str().map(ToString::to_string))
+                    .collect(),
+                Bson::String(t) => vec![t.clone()],
+                _ => return None,
+            })),
+            "$$matchesAny" => match value {
+                Bson::Array(alternatives) => Some(Operator::MatchesAny(alternatives.clone())),
+                _ => None,
+            },
+            "$$unsetOrMatches" => Some(Operator::UnsetOrMatches(value.clone())),
+            _ => None,
+        }
+    }
+
+    fn matches(&self, actual: Option<&Bson>) -> bool {
+        match self {
+            Operator::Exists(should_exist) => actual.is_some() == *should_exist,
+            Operator::Type(types) => actual
+                .map(|actual| types.iter().any(|t| bson_type_name(actual) == t))
+                .unwrap_or(false),
+            Operator::MatchesAny(alternatives) => actual
+                .map(|actual| alternatives.iter().any(|alt| match_bson(actual, alt)))
+                .unwrap_or(false),
+            Operator::UnsetOrMatches(expected) => actual
+                .map(|actual| match_bson(actual, expected))
+                .unwrap_or(true),
+        }
+    }
+}
+
+fn bson_type_name(value: &Bson) -> &'static str {
+    match value {
+        Bson::Double(..) => "double",
+        Bson::String(..) => "string",
+        Bson::Document(..) => "object",
+        Bson::Array(..) => "array",
+        Bson::Binary(..) => "binData",
+        Bson::Boolean(..) => "bool",
+        Bson::Null => "null",
+        Bson::RegularExpression(..) => "regex",
+        Bson::JavaScriptCode(..) => "javascript",
+        Bson::Int32(..) => "int",
+        Bson::Int64(..) => "long",
+        Bson::Timestamp(..) => "timestamp",
+        Bson::ObjectId(..) => "objectId",
+        Bson::DateTime(..) => "date",
+        Bson::Symbol(..) => "symbol",
+        Bson::Decimal128(..) => "decimal",
+        Bson::Undefined => "undefined",
+        Bson::MaxKey => "maxKey",
+        Bson::MinKey => "minKey",
+        Bson::JavaScriptCodeWithScope(..) => "javascriptWithScope",
+        Bson::DbPointer(..) => "dbPointer",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bson::{doc, Bson};
+
+    use super::Matchable;
+
+    #[test]
+    fn structural_match_ignores_extra_actual_fields() {
+        let actual = Bson::Document(doc! { "a": 1, "b": 2 });
+        let expected = Bson::Document(doc! { "a": 1 });
+        assert!(actual.matches(&expected));
+    }
+
+    #[test]
+    fn structural_mismatch_fails() {
+        let actual = Bson::Document(doc! { "a": 1 });
+        let expected = Bson::Document(doc! { "a": 2 });
+        assert!(!actual.matches(&expected));
+    }
+
+    #[test]
+    fn exists_operator() {
+        let present = Bson::Document(doc! { "a": 1 });
+        let absent = Bson::Document(doc! {});
+
+        assert!(present.matches(&Bson::Document(doc! { "a": { "$$exists": true } })));
+        assert!(!absent.matches(&Bson::Document(doc! { "a": { "$$exists": true } })));
+        assert!(absent.matches(&Bson::Document(doc! { "a": { "$$exists": false } })));
+    }
+
+    #[test]
+    fn type_operator() {
+        let actual = Bson::Document(doc! { "a": "hello" });
+        assert!(actual.matches(&Bson::Document(doc! { "a": { "$$type": "string" } })));
+        assert!(actual.matches(&Bson::Document(doc! { "a": { "$$type": ["int", "string"] } })));
+        assert!(!actual.matches(&Bson::Document(doc! { "a": { "$$type": "int" } })));
+    }
+
+    #[test]
+    fn matches_any_operator() {
+        let actual = Bson::Document(doc! { "a": 2 });
+        assert!(actual.matches(&Bson::Document(doc! { "a": { "$$matchesAny": [1, 2, 3] } })));
+        assert!(!actual.matches(&Bson::Document(doc! { "a": { "$$matchesAny": [1, 3] } })));
+    }
+
+    #[test]
+    fn unset_or_matches_operator() {
+        let present = Bson::Document(doc! { "a": 1 });
+        let absent = Bson::Document(doc! {});
+        let schema = Bson::Document(doc! { "a": { "$$unsetOrMatches": 1 } });
+
+        assert!(present.matches(&schema));
+        assert!(absent.matches(&schema));
+        assert!(!present.matches(&Bson::Document(doc! { "a": { "$$unsetOrMatches": 2 } })));
+    }
+}
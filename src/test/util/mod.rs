@@ -1,10 +1,12 @@
+mod bench;
 mod event;
 mod failpoint;
 mod lock;
 mod matchable;
 
 pub use self::{
-    event::{CommandEvent, EventClient},
+    bench::{run_benchmark, BenchmarkOptions, BenchmarkReport, Workload},
+    event::{CommandEvent, EventClient, MetricsRecorder},
     failpoint::{FailCommandOptions, FailPoint, FailPointMode},
     lock::TestLock,
     matchable::{assert_matches, Matchable},
@@ -19,11 +21,13 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use self::event::EventHandler;
 use super::CLIENT_OPTIONS;
 use crate::{
+    encryption::{ClientEncryption, ClientEncryptionOptions, EncryptionSchema, KeyVaultClient, KmsProvider},
     error::{CommandError, ErrorKind, Result},
     operation::RunCommand,
     options::{AuthMechanism, ClientOptions, CollectionOptions, CreateCollectionOptions},
     Client,
     Collection,
+    Namespace,
 };
 use failpoint::FailPointGuard;
 
@@ -33,6 +37,7 @@ pub struct TestClient {
     pub options: ClientOptions,
     pub server_info: IsMasterCommandResponse,
     pub server_version: Version,
+    encryption: Option<Arc<ClientEncryption>>,
 }
 
 impl std::ops::Deref for TestClient {
@@ -99,6 +104,7 @@ impl TestClient {
             options,
             server_info,
             server_version,
+            encryption: None,
         }
     }
 
@@ -281,6 +287,80 @@ impl TestClient {
         let coll = self.get_coll(db_name, coll_name);
         drop_collection(&coll).await;
     }
+
+    /// Provision a fresh key vault collection, including its unique `keyAltNames` index, for
+    /// CSFLE tests to create DEKs in.
+    pub async fn init_key_vault(&self, namespace: &Namespace) -> Result<KeyVaultClient> {
+        let coll = self
+            .init_db_and_typed_coll(&namespace.db, &namespace.coll)
+            .await;
+        let key_vault = KeyVaultClient::new(coll);
+        key_vault.create_key_vault_index().await?;
+        Ok(key_vault)
+    }
+
+    /// Create a local-provider DEK in `key_vault`, for tests that need a key id to build an
+    /// [`EncryptionSchema`] around without caring about KMS wrapping details.
+    pub async fn create_local_data_key(
+        &self,
+        key_vault: &KeyVaultClient,
+        master_key: [u8; crate::encryption::LOCAL_MASTER_KEY_LEN],
+    ) -> Result<Bson> {
+        let kms_provider = KmsProvider::Local { master_key };
+        let id = key_vault.create_data_key(&kms_provider, vec![]).await?;
+        Ok(Bson::Binary(id))
+    }
+
+    /// Build a [`TestClient`] that transparently encrypts/decrypts the fields named in `schema`
+    /// via [`Self::insert_one_encrypted`]/[`Self::find_one_decrypted`].
+    ///
+    /// `ClientOptions` has no hook for intercepting every operation's wire documents, so rather
+    /// than a universal interceptor this attaches a [`ClientEncryption`] directly to the
+    /// `TestClient` and the two methods above consult it explicitly.
+    pub async fn with_encryption_schema(
+        key_vault_namespace: Namespace,
+        kms_provider: KmsProvider,
+        schema: EncryptionSchema,
+    ) -> Self {
+        let mut client = Self::new().await;
+        let key_vault = client.init_key_vault(&key_vault_namespace).await.unwrap();
+
+        client.encryption = Some(Arc::new(ClientEncryption::new(
+            key_vault,
+            ClientEncryptionOptions {
+                key_vault_namespace,
+                kms_provider,
+                schema,
+            },
+        )));
+
+        client
+    }
+
+    /// Insert `document` into `coll`, encrypting any fields named in this client's encryption
+    /// schema first. Behaves like a plain `insert_one` if the client has no schema attached.
+    pub async fn insert_one_encrypted(&self, coll: &Collection, mut document: crate::bson::Document) -> Result<()> {
+        if let Some(encryption) = &self.encryption {
+            encryption.encrypt_document(&mut document).await?;
+        }
+        coll.insert_one(document, None).await?;
+        Ok(())
+    }
+
+    /// Find one document in `coll` matching `filter`, decrypting any encrypted fields named in
+    /// this client's encryption schema. Behaves like a plain `find_one` if the client has no
+    /// schema attached.
+    pub async fn find_one_decrypted(
+        &self,
+        coll: &Collection,
+        filter: impl Into<Option<crate::bson::Document>>,
+    ) -> Result<Option<crate::bson::Document>> {
+        let mut result = coll.find_one(filter, None).await?;
+        if let (Some(encryption), Some(document)) = (&self.encryption, result.as_mut()) {
+            encryption.decrypt_document(document).await?;
+        }
+        Ok(result)
+    }
 }
 
 pub async fn drop_collection<T>(coll: &Collection<T>)